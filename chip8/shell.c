@@ -31,7 +31,7 @@ unsigned char fontset[80] = {
 unsigned char memory[4096] = {0};
 
 // display of 32x64
-bool display[SCREEN_WIDTH][SCREEN_HEIGHT] = {false};
+bool display[SCREEN_HEIGHT][SCREEN_WIDTH] = {false};
 
 // Use this for the call stack, early ones apparently had space for two
 // instructions, I went a bit overboard and have space for 32 16-bit addresses
@@ -53,7 +53,7 @@ void clear_screen (bool display[SCREEN_HEIGHT][SCREEN_WIDTH]) {
     int i, j;
     for (i = 0; i < SCREEN_HEIGHT; i++) {
         for (j = 0; j < SCREEN_WIDTH; j++) {
-            display[j][i] = false;
+            display[i][j] = false;
         }
     }
     printf("clearing");
@@ -1,5 +1,6 @@
 #include <stdio.h>
 #include <stdlib.h>
+#include <limits.h>
 
 /*
 * the codebuffer will be a pointer to the 8080 hexdumped code
@@ -287,18 +288,48 @@ int disassembler8080(unsigned char *codebuffer, int pc) {
 
 
 int main (int argc, char**argv) {
+    if (argc < 2) {
+        printf("usage: %s <rom file>\n", argv[0]);
+        exit(1);
+    }
+
     FILE *f= fopen(argv[1], "rb");
     if (f==NULL){
         printf("error: Couldn't open %s\n", argv[1]);
+        exit(1);
     }
 
     fseek(f, 0L, SEEK_END);
-    int fsize = ftell(f);
+    long fsize = ftell(f);
     fseek(f, 0L, SEEK_SET);
 
-    unsigned char *buffer=malloc(fsize);
+    if (fsize < 0) {
+        printf("error: Couldn't get the size of %s\n", argv[1]);
+        fclose(f);
+        exit(1);
+    }
+
+    // pc is an int, so anything bigger can't be walked safely
+    if (fsize > INT_MAX - 2) {
+        printf("error: %s is too big to disassemble\n", argv[1]);
+        fclose(f);
+        exit(1);
+    }
+
+    // Pad with two zeroed bytes so a 2 or 3 byte opcode at the very end of
+    // the file doesn't read its operands out of bounds
+    unsigned char *buffer=calloc(fsize + 2, 1);
+    if (buffer == NULL) {
+        printf("error: Couldn't allocate memory for %s\n", argv[1]);
+        fclose(f);
+        exit(1);
+    }
     
-    fread(buffer, fsize, 1, f);
+    if (fsize > 0 && fread(buffer, fsize, 1, f) != 1) {
+        printf("error: Couldn't read %s\n", argv[1]);
+        fclose(f);
+        exit(1);
+    }
     fclose(f);
 
     int pc = 0;
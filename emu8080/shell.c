@@ -70,11 +70,14 @@ void ArithFlagsA(State8080 *state, uint16_t res)
 	state->cc.p = parity(res&0xff, 8);
 }
 
+// Only for documented opcodes that haven't been written yet, the undocumented
+// ones are run as the NOP/JMP/RET/CALL they alias on real hardware
 void UnimplementedInstruction(State8080* state)
 {
 	//pc will have advanced one, so undo that
-	printf ("Error: Unimplemented instruction\n");
 	state->pc--;
+	printf ("Error: Unimplemented instruction $%02x at $%04x\n",
+			state->memory[state->pc], state->pc);
 	printf("\n");
 	exit(1);
 }
@@ -111,7 +114,7 @@ int Emulate8080Op(State8080* state)
 			state->pc++;
 			break;
 		case 0x07: UnimplementedInstruction(state); break;
-		case 0x08: UnimplementedInstruction(state); break;
+		case 0x08: break;	//NOP (undocumented)
 		case 0x09: 							//DAD B
 			{
 			uint32_t hl = (state->h << 8) | state->l;
@@ -145,7 +148,7 @@ int Emulate8080Op(State8080* state)
 				state->cc.cy = (1 == (x&1));
 			}
 			break;
-		case 0x10: UnimplementedInstruction(state); break;
+		case 0x10: break;	//NOP (undocumented)
 		case 0x11: 							//LXI	D,word
 			state->e = opcode[1];
 			state->d = opcode[2];
@@ -161,7 +164,7 @@ int Emulate8080Op(State8080* state)
 		case 0x15: UnimplementedInstruction(state); break;
 		case 0x16: UnimplementedInstruction(state); break;
 		case 0x17: UnimplementedInstruction(state); break;
-		case 0x18: UnimplementedInstruction(state); break;
+		case 0x18: break;	//NOP (undocumented)
 		case 0x19: 							//DAD    D
 			{
 			uint32_t hl = (state->h << 8) | state->l;
@@ -183,7 +186,7 @@ int Emulate8080Op(State8080* state)
 		case 0x1d: UnimplementedInstruction(state); break;
 		case 0x1e: UnimplementedInstruction(state); break;
 		case 0x1f: UnimplementedInstruction(state); break;
-		case 0x20: UnimplementedInstruction(state); break;
+		case 0x20: break;	//NOP (undocumented)
 		case 0x21: 							//LXI	H,word
 			state->l = opcode[1];
 			state->h = opcode[2];
@@ -202,7 +205,7 @@ int Emulate8080Op(State8080* state)
 			state->pc++;
 			break;
 		case 0x27: UnimplementedInstruction(state); break;
-		case 0x28: UnimplementedInstruction(state); break;
+		case 0x28: break;	//NOP (undocumented)
 		case 0x29: 								//DAD    H
 			{
 			uint32_t hl = (state->h << 8) | state->l;
@@ -218,7 +221,7 @@ int Emulate8080Op(State8080* state)
 		case 0x2d: UnimplementedInstruction(state); break;
 		case 0x2e: UnimplementedInstruction(state); break;
 		case 0x2f: UnimplementedInstruction(state); break;
-		case 0x30: UnimplementedInstruction(state); break;
+		case 0x30: break;	//NOP (undocumented)
 		case 0x31: 							//LXI	SP,word
 			state->sp = (opcode[2]<<8) | opcode[1];
 			state->pc += 2;
@@ -242,7 +245,7 @@ int Emulate8080Op(State8080* state)
 			}
 			break;
 		case 0x37: UnimplementedInstruction(state); break;
-		case 0x38: UnimplementedInstruction(state); break;
+		case 0x38: break;	//NOP (undocumented)
 		case 0x39: UnimplementedInstruction(state); break;
 		case 0x3a: 							//LDA    (word)
 			{
@@ -416,7 +419,7 @@ int Emulate8080Op(State8080* state)
 		case 0xc1: 						//POP    B
 			{
 				state->c = state->memory[state->sp];
-				state->b = state->memory[state->sp+1];
+				state->b = state->memory[(uint16_t)(state->sp+1)];
 				state->sp += 2;
 			}
 			break;
@@ -432,8 +435,8 @@ int Emulate8080Op(State8080* state)
 		case 0xc4: UnimplementedInstruction(state); break;
 		case 0xc5: 						//PUSH   B
 			{
-			state->memory[state->sp-1] = state->b;
-			state->memory[state->sp-2] = state->c;
+			state->memory[(uint16_t)(state->sp-1)] = state->b;
+			state->memory[(uint16_t)(state->sp-2)] = state->c;
 			state->sp = state->sp - 2;
 			}
 			break;
@@ -451,17 +454,19 @@ int Emulate8080Op(State8080* state)
 		case 0xc7: UnimplementedInstruction(state); break;
 		case 0xc8: UnimplementedInstruction(state); break;
 		case 0xc9: 						//RET
-			state->pc = state->memory[state->sp] | (state->memory[state->sp+1] << 8);
+			state->pc = state->memory[state->sp] | (state->memory[(uint16_t)(state->sp+1)] << 8);
 			state->sp += 2;
 			break;
 		case 0xca: UnimplementedInstruction(state); break;
-		case 0xcb: UnimplementedInstruction(state); break;
+		case 0xcb:						//JMP address (undocumented)
+			state->pc = (opcode[2] << 8) | opcode[1];
+			break;
 		case 0xcc: UnimplementedInstruction(state); break;
 		case 0xcd: 						//CALL adr
 			{
 			uint16_t	ret = state->pc+2;
-			state->memory[state->sp-1] = (ret >> 8) & 0xff;
-			state->memory[state->sp-2] = (ret & 0xff);
+			state->memory[(uint16_t)(state->sp-1)] = (ret >> 8) & 0xff;
+			state->memory[(uint16_t)(state->sp-2)] = (ret & 0xff);
 			state->sp = state->sp - 2;
 			state->pc = (opcode[2] << 8) | opcode[1];
 			}
@@ -472,7 +477,7 @@ int Emulate8080Op(State8080* state)
 		case 0xd1: 						//POP    D
 			{
 				state->e = state->memory[state->sp];
-				state->d = state->memory[state->sp+1];
+				state->d = state->memory[(uint16_t)(state->sp+1)];
 				state->sp += 2;
 			}
 			break;
@@ -484,26 +489,37 @@ int Emulate8080Op(State8080* state)
 		case 0xd4: UnimplementedInstruction(state); break;
 		case 0xd5: 						//PUSH   D
 			{
-			state->memory[state->sp-1] = state->d;
-			state->memory[state->sp-2] = state->e;
+			state->memory[(uint16_t)(state->sp-1)] = state->d;
+			state->memory[(uint16_t)(state->sp-2)] = state->e;
 			state->sp = state->sp - 2;
 			}
 			break;
 		case 0xd6: UnimplementedInstruction(state); break;
 		case 0xd7: UnimplementedInstruction(state); break;
 		case 0xd8: UnimplementedInstruction(state); break;
-		case 0xd9: UnimplementedInstruction(state); break;
+		case 0xd9: 						//RET (undocumented)
+			state->pc = state->memory[state->sp] | (state->memory[(uint16_t)(state->sp+1)] << 8);
+			state->sp += 2;
+			break;
 		case 0xda: UnimplementedInstruction(state); break;
 		case 0xdb: UnimplementedInstruction(state); break;
 		case 0xdc: UnimplementedInstruction(state); break;
-		case 0xdd: UnimplementedInstruction(state); break;
+		case 0xdd: 						//CALL adr (undocumented)
+			{
+			uint16_t	ret = state->pc+2;
+			state->memory[(uint16_t)(state->sp-1)] = (ret >> 8) & 0xff;
+			state->memory[(uint16_t)(state->sp-2)] = (ret & 0xff);
+			state->sp = state->sp - 2;
+			state->pc = (opcode[2] << 8) | opcode[1];
+			}
+			break;
 		case 0xde: UnimplementedInstruction(state); break;
 		case 0xdf: UnimplementedInstruction(state); break;
 		case 0xe0: UnimplementedInstruction(state); break;
 		case 0xe1: 					//POP    H
 			{
 				state->l = state->memory[state->sp];
-				state->h = state->memory[state->sp+1];
+				state->h = state->memory[(uint16_t)(state->sp+1)];
 				state->sp += 2;
 			}
 			break;
@@ -512,8 +528,8 @@ int Emulate8080Op(State8080* state)
 		case 0xe4: UnimplementedInstruction(state); break;
 		case 0xe5: 						//PUSH   H
 			{
-			state->memory[state->sp-1] = state->h;
-			state->memory[state->sp-2] = state->l;
+			state->memory[(uint16_t)(state->sp-1)] = state->h;
+			state->memory[(uint16_t)(state->sp-2)] = state->l;
 			state->sp = state->sp - 2;
 			}
 			break;
@@ -539,13 +555,21 @@ int Emulate8080Op(State8080* state)
 			}
 			break;
 		case 0xec: UnimplementedInstruction(state); break;
-		case 0xed: UnimplementedInstruction(state); break;
+		case 0xed: 						//CALL adr (undocumented)
+			{
+			uint16_t	ret = state->pc+2;
+			state->memory[(uint16_t)(state->sp-1)] = (ret >> 8) & 0xff;
+			state->memory[(uint16_t)(state->sp-2)] = (ret & 0xff);
+			state->sp = state->sp - 2;
+			state->pc = (opcode[2] << 8) | opcode[1];
+			}
+			break;
 		case 0xee: UnimplementedInstruction(state); break;
 		case 0xef: UnimplementedInstruction(state); break;
 		case 0xf0: UnimplementedInstruction(state); break;
 		case 0xf1: 					//POP PSW
 			{
-				state->a = state->memory[state->sp+1];
+				state->a = state->memory[(uint16_t)(state->sp+1)];
 				uint8_t psw = state->memory[state->sp];
 				state->cc.z  = (0x01 == (psw & 0x01));
 				state->cc.s  = (0x02 == (psw & 0x02));
@@ -560,13 +584,13 @@ int Emulate8080Op(State8080* state)
 		case 0xf4: UnimplementedInstruction(state); break;
 		case 0xf5: 						//PUSH   PSW
 			{
-			state->memory[state->sp-1] = state->a;
+			state->memory[(uint16_t)(state->sp-1)] = state->a;
 			uint8_t psw = (state->cc.z |
 							state->cc.s << 1 |
 							state->cc.p << 2 |
 							state->cc.cy << 3 |
 							state->cc.ac << 4 );
-			state->memory[state->sp-2] = psw;
+			state->memory[(uint16_t)(state->sp-2)] = psw;
 			state->sp = state->sp - 2;
 			}
 			break;
@@ -577,7 +601,15 @@ int Emulate8080Op(State8080* state)
 		case 0xfa: UnimplementedInstruction(state); break;
 		case 0xfb: state->int_enable = 1;  break;	//EI
 		case 0xfc: UnimplementedInstruction(state); break;
-		case 0xfd: UnimplementedInstruction(state); break;
+		case 0xfd: 						//CALL adr (undocumented)
+			{
+			uint16_t	ret = state->pc+2;
+			state->memory[(uint16_t)(state->sp-1)] = (ret >> 8) & 0xff;
+			state->memory[(uint16_t)(state->sp-2)] = (ret & 0xff);
+			state->sp = state->sp - 2;
+			state->pc = (opcode[2] << 8) | opcode[1];
+			}
+			break;
 		case 0xfe: 						//CPI  byte
 			{
 			uint8_t x = state->a - opcode[1];
@@ -610,18 +642,50 @@ void ReadFileIntoMemoryAt(State8080* state, char* filename, uint32_t offset)
 		exit(1);
 	}
 	fseek(f, 0L, SEEK_END);
-	int fsize = ftell(f);
+	long fsize = ftell(f);
 	fseek(f, 0L, SEEK_SET);
 	
+	if (fsize < 0)
+	{
+		printf("error: Couldn't get the size of %s\n", filename);
+		fclose(f);
+		exit(1);
+	}
+	
+	//don't let a ROM run off the end of the 64K address space
+	if (offset > 0x10000 || fsize > (long)(0x10000 - offset))
+	{
+		printf("error: %s doesn't fit in memory at $%04x\n", filename, offset);
+		fclose(f);
+		exit(1);
+	}
+	
 	uint8_t *buffer = &state->memory[offset];
-	fread(buffer, fsize, 1, f);
+	if (fsize > 0 && fread(buffer, fsize, 1, f) != 1)
+	{
+		printf("error: Couldn't read %s\n", filename);
+		fclose(f);
+		exit(1);
+	}
 	fclose(f);
 }
 
 State8080* Init8080(void)
 {
 	State8080* state = calloc(1,sizeof(State8080));
-	state->memory = malloc(0x10000);  //16K
+	if (state == NULL)
+	{
+		printf("error: Couldn't allocate the 8080 state\n");
+		exit(1);
+	}
+	//64K, zeroed so runs are repeatable, plus two bytes of padding so the
+	//operand fetch of an instruction at $fffe/$ffff stays in the buffer
+	state->memory = calloc(1, 0x10000 + 2);
+	if (state->memory == NULL)
+	{
+		printf("error: Couldn't allocate 8080 memory\n");
+		exit(1);
+	}
 	return state;
 }
 